@@ -0,0 +1,216 @@
+// Factorization built on top of a smallest-prime-factor (SPF) sieve. The SPF
+// sieve itself is built on top of the segmented engine in
+// `segmented_sieve.rs`: same base primes, same windowed striking with
+// offsets carried across windows, just recording *which* prime did the
+// striking instead of flipping a composite bit. Since base primes are
+// struck in increasing order, the first one to mark an index is always its
+// smallest factor.
+
+use crate::segmented_sieve::{self, BasePrime, SEGMENT_SPAN};
+use crate::{is_prime_mr, Primes};
+
+/// Above this, `factorize` doesn't sieve at all: it checks `n` with
+/// `is_prime_mr`, then trial-divides it by generated primes up to its
+/// square root.
+const SPF_SIEVE_LIMIT: u64 = 1 << 20;
+
+/// Builds a smallest-prime-factor table: `result[i]` is the smallest prime
+/// factor of `i`, for `i` in `0..=limit`. `result[0]` is `0` and `result[1]`
+/// is `1`, as sentinels, since neither has a prime factor.
+pub fn least_factor_sieve(limit: u64) -> Vec<u32> {
+    let mut spf = vec![0u32; limit as usize + 1];
+    if limit >= 1 {
+        spf[1] = 1;
+    }
+    if limit < 2 {
+        return spf;
+    }
+
+    let sqrt_limit = (limit as f64).sqrt() as u64 + 1;
+    let mut base_primes: Vec<BasePrime> = segmented_sieve::base_primes(sqrt_limit.min(limit))
+        .into_iter()
+        .map(|prime| BasePrime {
+            prime,
+            next_multiple: prime * prime,
+        })
+        .collect();
+
+    let mut window_lo = 2u64;
+    while window_lo <= limit {
+        let window_hi = (window_lo + SEGMENT_SPAN).min(limit + 1); // exclusive
+
+        for bp in &mut base_primes {
+            let mut m = bp.next_multiple;
+            while m < window_hi {
+                let idx = m as usize;
+                if spf[idx] == 0 {
+                    spf[idx] = bp.prime as u32;
+                }
+                m += bp.prime;
+            }
+            bp.next_multiple = m;
+        }
+
+        // Anything this window's base primes never struck has no factor
+        // below sqrt(limit), so it's prime: its own smallest factor.
+        for n in window_lo..window_hi {
+            if spf[n as usize] == 0 {
+                spf[n as usize] = n as u32;
+            }
+        }
+
+        window_lo = window_hi;
+    }
+
+    spf
+}
+
+/// Factors `n` into prime/exponent pairs in increasing order of prime, e.g.
+/// `factorize(360) == vec![(2, 3), (3, 2), (5, 1)]`. `factorize(0)` and
+/// `factorize(1)` return an empty vector, since neither has prime factors.
+pub fn factorize(n: u64) -> Vec<(u64, u32)> {
+    if n <= 1 {
+        return Vec::new();
+    }
+
+    if n <= SPF_SIEVE_LIMIT {
+        factorize_with_spf(n, &least_factor_sieve(n))
+    } else {
+        factorize_by_trial_division(n)
+    }
+}
+
+// Walk down the chain dividing by `spf[n]` repeatedly. Because the smallest
+// factor is always produced first, duplicate factors come out grouped, so
+// exponents can be accumulated with a running counter instead of a hash map.
+fn factorize_with_spf(mut n: u64, spf: &[u32]) -> Vec<(u64, u32)> {
+    let mut factors = Vec::new();
+
+    while n > 1 {
+        let p = spf[n as usize] as u64;
+        let mut exponent = 0u32;
+        while n.is_multiple_of(p) {
+            n /= p;
+            exponent += 1;
+        }
+        factors.push((p, exponent));
+    }
+
+    factors
+}
+
+// For n above SPF_SIEVE_LIMIT, trial-divide by generated primes up to
+// sqrt(n) instead of sieving the whole range. n itself is checked with
+// is_prime_mr first, since for large prime n that's the whole answer in
+// O(log n) rather than a trial-division scan all the way to sqrt(n).
+fn factorize_by_trial_division(mut n: u64) -> Vec<(u64, u32)> {
+    if is_prime_mr(n) {
+        return vec![(n, 1)];
+    }
+
+    let mut factors = Vec::new();
+
+    for p in Primes::new() {
+        if p * p > n {
+            break;
+        }
+        if n.is_multiple_of(p) {
+            let mut exponent = 0u32;
+            while n.is_multiple_of(p) {
+                n /= p;
+                exponent += 1;
+            }
+            factors.push((p, exponent));
+        }
+    }
+
+    if n > 1 {
+        factors.push((n, 1));
+    }
+
+    factors
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn least_factor_sieve_handles_tiny_limits() {
+        assert_eq!(least_factor_sieve(0), vec![0]);
+        assert_eq!(least_factor_sieve(1), vec![0, 1]);
+        assert_eq!(least_factor_sieve(2), vec![0, 1, 2]);
+    }
+
+    #[test]
+    fn least_factor_sieve_matches_naive_trial_division() {
+        fn naive_smallest_factor(n: u64) -> u32 {
+            let mut d = 2;
+            while d * d <= n {
+                if n.is_multiple_of(d) {
+                    return d as u32;
+                }
+                d += 1;
+            }
+            n as u32
+        }
+
+        let limit = 2_000u64;
+        let spf = least_factor_sieve(limit);
+        for n in 2..=limit {
+            assert_eq!(spf[n as usize], naive_smallest_factor(n), "mismatch at n = {}", n);
+        }
+    }
+
+    #[test]
+    fn least_factor_sieve_crosses_a_window_boundary() {
+        // SEGMENT_SPAN integers per window, so this limit forces two full
+        // windows plus a partial one, exercising the carried
+        // `next_multiple` offsets.
+        let limit = segmented_sieve::SEGMENT_SPAN * 2 + 37;
+        let spf = least_factor_sieve(limit);
+        for n in 2..=limit {
+            let p = spf[n as usize] as u64;
+            assert!(p >= 2 && n.is_multiple_of(p), "spf[{}] = {} doesn't divide it", n, p);
+        }
+    }
+
+    #[test]
+    fn factorize_handles_zero_and_one() {
+        assert_eq!(factorize(0), Vec::new());
+        assert_eq!(factorize(1), Vec::new());
+    }
+
+    #[test]
+    fn factorize_known_values() {
+        assert_eq!(factorize(360), vec![(2, 3), (3, 2), (5, 1)]);
+        assert_eq!(factorize(97), vec![(97, 1)]);
+        assert_eq!(factorize(2), vec![(2, 1)]);
+    }
+
+    #[test]
+    fn factorize_around_the_spf_sieve_limit_boundary() {
+        let just_below = SPF_SIEVE_LIMIT - 1;
+        let just_above = SPF_SIEVE_LIMIT + 1;
+
+        let below = factorize(just_below);
+        let above = factorize(just_above);
+
+        assert_eq!(below.iter().map(|&(p, e)| p.pow(e)).product::<u64>(), just_below);
+        assert_eq!(above.iter().map(|&(p, e)| p.pow(e)).product::<u64>(), just_above);
+    }
+
+    #[test]
+    fn factorize_large_semiprime_via_trial_division() {
+        // 1,000,003 * 1,000,033, both prime, well above SPF_SIEVE_LIMIT.
+        assert_eq!(factorize(1_000_036_000_099), vec![(1_000_003, 1), (1_000_033, 1)]);
+    }
+
+    #[test]
+    fn factorize_large_prime_takes_the_is_prime_mr_fast_path() {
+        // Largest prime below 2^64; trial division up to its ~4.3e9 sqrt
+        // would take far too long for a unit test, so this only finishes if
+        // factorize actually checks is_prime_mr before trial-dividing.
+        assert_eq!(factorize(18_446_744_073_709_551_557), vec![(18_446_744_073_709_551_557, 1)]);
+    }
+}
@@ -0,0 +1,479 @@
+//! An incremental prime number generator, exposed as a library so it can be
+//! used for more than "print the millionth prime": checking individual
+//! numbers for primality, pulling primes out of a range, or counting how
+//! many fall between two bounds.
+//!
+//! The engine is the same `Wheel`/`Filter`-driven generator the crate always
+//! used; this module just lifts it out of `main()` and wraps it in a
+//! friendlier surface. `SegmentedSieve` (see that module) is the alternative
+//! to reach for when the upper bound is known up front.
+
+use priority_queue::PriorityQueue;
+use std::collections::VecDeque;
+
+pub mod factor;
+pub mod primality;
+pub mod segmented_sieve;
+
+pub use factor::{factorize, least_factor_sieve};
+pub use primality::is_prime_mr;
+
+/// Below this, `is_prime` sieves (and `PrimeSet::is_prime` caches the
+/// result); above it, generating every prime up to n is wasteful, so both
+/// dispatch to `is_prime_mr` instead.
+const IS_PRIME_SIEVE_LIMIT: u64 = 1 << 20;
+
+// A "filter" (nothing official here, just sounds good to me) is a sequence of
+// multiples of some prime. In the Sieve of Eratosthenes, it's the sequence of
+// "crossed out numbers" (p, 2p, 3p, ...) for any prime p.
+//
+// This could easily be made an Iterator, but we don't use that functionality so
+// we leave it out.
+#[derive(Hash, Copy, Clone, Eq, PartialEq)]
+struct Filter {
+    base: u64,
+    state: u64,
+}
+
+impl Filter {
+    // A new filter could naively start at p, but we can do better. We know that
+    // p^2 is the first number in the filter that is not a multiple of any
+    // smaller prime. So we start there.
+    fn new(base: u64) -> Filter {
+        Filter {
+            base,
+            state: base * base,
+        }
+    }
+
+    fn step(&mut self) -> u64 {
+        self.state += self.base;
+        self.state
+    }
+}
+
+// Naively we'd check every integer. But we can avoid checking even numbers by
+// instead adding 2 at each step. To also avoid checking multiples of 3, we'd
+// alternate adding 2 and 4.
+//
+// This is called a _wheel_ of size 2, with the pattern [2, 4]. We can
+// generalize this to wheels of size n, with the pattern [2, 4, 2, 4, 6, 2...]
+//
+// At a point there are dimishing returns to increasing the size of the wheel,
+// because prime number become less dense. But for small wheels, the speedup is
+// significant.
+//
+// We use a wheel of size 48, which allows us to avoid checking multiples of 2,
+// 3, 5, and 7.
+const WHEEL_STATES: [u64; 48] = [
+    2, 4, 2, 4, 6, 2, 6, 4, 2, 4, 6, 6, 2, 6, 4, 2, 6, 4, 6, 8, 4, 2, 4, 2, 4, 8, 6, 4, 6, 2, 4, 6,
+    2, 6, 6, 4, 2, 4, 6, 2, 6, 4, 2, 4, 2, 10, 2, 10,
+];
+
+struct Wheel {
+    gaps: Vec<u64>,
+    index: usize,
+    state: u64,
+}
+
+impl Wheel {
+    fn new() -> Wheel {
+        // The size-48 wheel above is exactly `Wheel::with_basis(&[2, 3, 5,
+        // 7])`, computed at runtime; we keep it as a literal table so the
+        // default case pays no setup cost.
+        Wheel::from_gaps(WHEEL_STATES.to_vec())
+    }
+
+    /// Build a wheel at runtime from an arbitrary set of small base primes,
+    /// e.g. `Wheel::with_basis(&[2, 3, 5, 7, 11, 13])` for a mod-30030 wheel
+    /// that also skips multiples of 11 and 13. Larger bases trade memory
+    /// (the gap table) for fewer candidates checked.
+    ///
+    /// Computes the modulus `M = product(basis)`, enumerates the residues in
+    /// `1..=M` coprime to every basis prime, and stores the successive gaps
+    /// between them (wrapping from the last residue back to the first, plus
+    /// `M`) as the cycle the wheel steps through.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `basis` is empty, contains a non-prime entry, or contains a
+    /// duplicate entry.
+    pub fn with_basis(basis: &[u64]) -> Wheel {
+        assert!(!basis.is_empty(), "basis must not be empty");
+        assert!(
+            basis.iter().all(|&p| is_prime_mr(p)),
+            "basis entries must be primes, got {:?}",
+            basis
+        );
+        let mut sorted_basis = basis.to_vec();
+        sorted_basis.sort_unstable();
+        sorted_basis.dedup();
+        assert!(
+            sorted_basis.len() == basis.len(),
+            "basis entries must be distinct, got {:?}",
+            basis
+        );
+
+        let modulus: u64 = basis.iter().product();
+        let residues: Vec<u64> = (1..=modulus)
+            .filter(|r| basis.iter().all(|&p| r % p != 0))
+            .collect();
+
+        let mut gaps = Vec::with_capacity(residues.len());
+        for pair in residues.windows(2) {
+            gaps.push(pair[1] - pair[0]);
+        }
+        gaps.push(modulus + residues[0] - residues[residues.len() - 1]);
+
+        // `from_gaps` starts at residue 1 with the index already at the
+        // last slot, so that slot needs to hold the gap *out of* residue 1
+        // (normally the first gap) rather than the wraparound gap.
+        gaps.rotate_left(1);
+
+        Wheel::from_gaps(gaps)
+    }
+
+    fn from_gaps(gaps: Vec<u64>) -> Wheel {
+        let last = gaps.len() - 1;
+        Wheel {
+            // Hack: start at residue 1 with the index on the last slot, so the
+            // first step lands on the next residue and leaves the index at 0
+            index: last,
+            state: 1,
+            gaps,
+        }
+    }
+
+    fn next(&mut self) -> u64 {
+        self.state += self.gaps[self.index];
+
+        if self.index == self.gaps.len() - 1 {
+            self.index = 0;
+        } else {
+            self.index += 1;
+        }
+
+        self.state
+    }
+}
+
+const SMALL_PRIMES: [u64; 4] = [2, 3, 5, 7];
+
+// The BiggerPrimes struct is an iterator over prime numbers. It maintains a list of
+// active filters, and a queue of filters (really a VecDeque) that are waiting
+// to be activated.
+//
+// This queue is helpful because it's inefficient to constantly search a filter
+// we know won't be useful until we're at the square of its base
+struct BiggerPrimes {
+    state: Wheel,
+    active_filters: PriorityQueue<Filter, u64>,
+    queued_filters: VecDeque<Filter>,
+}
+
+impl BiggerPrimes {
+    pub fn new() -> BiggerPrimes {
+        BiggerPrimes::with_wheel(Wheel::new())
+    }
+
+    fn with_wheel(wheel: Wheel) -> BiggerPrimes {
+        BiggerPrimes {
+            state: wheel,
+            active_filters: PriorityQueue::new(),
+            queued_filters: VecDeque::new(),
+        }
+    }
+
+    fn step(&mut self) -> Option<u64> {
+        let n = self.state.next();
+
+        // If any active filter matches, we're not prime
+        while let Some((f, _)) = self.active_filters.peek() {
+            match f.state {
+                x if x < n => {
+                    let mut f = self.active_filters.pop().unwrap().0;
+                    f.step();
+                    self.active_filters.push(f, f.state.wrapping_neg());
+                }
+                x if x == n => {
+                    return None;
+                }
+                _ => {
+                    break;
+                }
+            }
+        }
+
+        // Update queued filters. The first entry is always p^2, so at most one will need updating
+        if n == self.queued_filters.front().map(|f| f.state).unwrap_or(0) {
+            let f = self.queued_filters.pop_front().unwrap();
+            self.active_filters.push(f, f.state.wrapping_neg());
+            return None;
+        }
+
+        // If we reach this point, we know we're at a prime number. So queue a
+        // new filter and return a Some
+        self.queued_filters.push_back(Filter::new(n));
+        Some(n)
+    }
+}
+
+impl Iterator for BiggerPrimes {
+    type Item = u64;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        while self.step().is_none() {}
+        Some(self.state.state)
+    }
+}
+
+/// An iterator over all prime numbers, starting from 2, with no upper bound.
+///
+/// `SMALL_PRIMES` is returned directly as a fast path; everything past that
+/// comes from the `Wheel`/`Filter`-driven `BiggerPrimes` generator. For the
+/// common case of "all primes up to some known limit", `SegmentedSieve` is
+/// usually faster — this type is for open-ended streams.
+pub struct Primes {
+    small_primes: std::vec::IntoIter<u64>,
+    bigger_primes: BiggerPrimes,
+}
+
+impl Primes {
+    pub fn new() -> Primes {
+        Primes {
+            small_primes: SMALL_PRIMES.to_vec().into_iter(),
+            bigger_primes: BiggerPrimes::new(),
+        }
+    }
+
+    /// Build a prime generator over a custom wheel basis, e.g. `&[2, 3, 5,
+    /// 7, 11, 13]`. The basis primes themselves are the fast path (in place
+    /// of the fixed `SMALL_PRIMES`) and come first, in the order given; the
+    /// wheel-driven generator then starts at the smallest number above 1
+    /// that is coprime to every basis prime. For a basis of the first few
+    /// primes in increasing order, that is just past the largest one; for
+    /// any other basis the primes are not yielded in increasing order.
+    pub fn with_basis(basis: &[u64]) -> Primes {
+        Primes {
+            // `Primes` owns its small-primes iterator, so a borrow of
+            // `basis` won't do here.
+            #[allow(clippy::unnecessary_to_owned)]
+            small_primes: basis.to_vec().into_iter(),
+            bigger_primes: BiggerPrimes::with_wheel(Wheel::with_basis(basis)),
+        }
+    }
+}
+
+impl Default for Primes {
+    fn default() -> Primes {
+        Primes::new()
+    }
+}
+
+impl Iterator for Primes {
+    type Item = u64;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        if let Some(prime) = self.small_primes.next() {
+            Some(prime)
+        } else {
+            self.bigger_primes.next()
+        }
+    }
+}
+
+/// A cache of primes discovered so far, backed by the incremental `Primes`
+/// generator.
+///
+/// Plain functions like `nth_prime` or `is_prime` each start a fresh
+/// generator, so repeated queries redo all the work below their argument.
+/// `PrimeSet` keeps the primes it has already found in a growable `Vec`, so
+/// later queries only need to generate what's missing.
+pub struct PrimeSet {
+    found: Vec<u64>,
+    source: Primes,
+}
+
+impl PrimeSet {
+    pub fn new() -> PrimeSet {
+        PrimeSet {
+            found: Vec::new(),
+            source: Primes::new(),
+        }
+    }
+
+    // Generate primes until at least `n` have been found.
+    fn ensure_at_least(&mut self, n: usize) {
+        while self.found.len() < n {
+            let p = self.source.next().expect("Primes is an unbounded iterator");
+            self.found.push(p);
+        }
+    }
+
+    // Generate primes until the largest found is >= limit (or limit is 0).
+    fn ensure_through(&mut self, limit: u64) {
+        while self.found.last().is_none_or(|&p| p < limit) {
+            let p = self.source.next().expect("Primes is an unbounded iterator");
+            self.found.push(p);
+        }
+    }
+
+    /// The `n`th prime, 1-indexed: `nth_prime(1) == 2`.
+    pub fn nth_prime(&mut self, n: usize) -> u64 {
+        assert!(n >= 1, "n must be at least 1");
+        self.ensure_at_least(n);
+        self.found[n - 1]
+    }
+
+    /// All primes in `[a, b)`.
+    pub fn primes_in_range(&mut self, a: u64, b: u64) -> impl Iterator<Item = u64> + '_ {
+        if b > 0 {
+            self.ensure_through(b - 1);
+        }
+        self.found.iter().copied().filter(move |&p| p >= a && p < b)
+    }
+
+    /// The number of primes in `[a, b)`, i.e. `pi(b) - pi(a)` where `pi(x)`
+    /// counts the primes below `x`.
+    pub fn count_in_range(&mut self, a: u64, b: u64) -> usize {
+        self.primes_in_range(a, b).count()
+    }
+
+    /// Whether `n` is prime. Dispatches to `is_prime_mr` for `n` above
+    /// `IS_PRIME_SIEVE_LIMIT`, since sieving up to such an `n` would be far
+    /// more work than a handful of Miller-Rabin rounds.
+    pub fn is_prime(&mut self, n: u64) -> bool {
+        if n < 2 {
+            return false;
+        }
+        if n > IS_PRIME_SIEVE_LIMIT {
+            return is_prime_mr(n);
+        }
+        self.ensure_through(n);
+        self.found.binary_search(&n).is_ok()
+    }
+}
+
+impl Default for PrimeSet {
+    fn default() -> PrimeSet {
+        PrimeSet::new()
+    }
+}
+
+/// The `n`th prime, 1-indexed: `nth_prime(1) == 2`.
+pub fn nth_prime(n: usize) -> u64 {
+    PrimeSet::new().nth_prime(n)
+}
+
+/// All primes in `[a, b)`.
+pub fn primes_in_range(a: u64, b: u64) -> impl Iterator<Item = u64> {
+    let mut set = PrimeSet::new();
+    if b > 0 {
+        set.ensure_through(b - 1);
+    }
+    set.found.into_iter().filter(move |&p| p >= a && p < b)
+}
+
+/// The number of primes in `[a, b)`, i.e. `pi(b) - pi(a)` where `pi(x)`
+/// counts the primes below `x`.
+pub fn count_in_range(a: u64, b: u64) -> usize {
+    PrimeSet::new().count_in_range(a, b)
+}
+
+/// Whether `n` is prime, dispatching to the sieve for small `n` and to
+/// `is_prime_mr` for large `n` so a membership check on a large `n` never
+/// needs to generate every prime up to `sqrt(n)`.
+pub fn is_prime(n: u64) -> bool {
+    PrimeSet::new().is_prime(n)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn with_basis_matches_default_wheel_for_its_own_basis() {
+        let default: Vec<u64> = Primes::new().take(200).collect();
+        let rebuilt: Vec<u64> = Primes::with_basis(&[2, 3, 5, 7]).take(200).collect();
+        assert_eq!(default, rebuilt);
+    }
+
+    #[test]
+    fn with_basis_larger_basis_still_matches() {
+        let default: Vec<u64> = Primes::new().take(200).collect();
+        let wider: Vec<u64> = Primes::with_basis(&[2, 3, 5, 7, 11, 13]).take(200).collect();
+        assert_eq!(default, wider);
+    }
+
+    #[test]
+    #[should_panic(expected = "basis must not be empty")]
+    fn with_basis_rejects_empty_basis() {
+        Wheel::with_basis(&[]);
+    }
+
+    #[test]
+    #[should_panic(expected = "basis entries must be primes")]
+    fn with_basis_rejects_basis_entries_below_two() {
+        Wheel::with_basis(&[2, 3, 1]);
+    }
+
+    #[test]
+    #[should_panic(expected = "basis entries must be primes")]
+    fn with_basis_rejects_composite_basis_entries() {
+        Wheel::with_basis(&[2, 3, 4]);
+    }
+
+    #[test]
+    #[should_panic(expected = "basis entries must be distinct")]
+    fn with_basis_rejects_duplicate_basis_entries() {
+        Wheel::with_basis(&[2, 3, 3, 5]);
+    }
+
+    #[test]
+    fn nth_prime_is_one_indexed() {
+        assert_eq!(nth_prime(1), 2);
+        assert_eq!(nth_prime(2), 3);
+        assert_eq!(nth_prime(6), 13);
+    }
+
+    #[test]
+    #[should_panic(expected = "n must be at least 1")]
+    fn nth_prime_rejects_zero() {
+        nth_prime(0);
+    }
+
+    #[test]
+    fn is_prime_handles_small_edge_cases() {
+        assert!(!is_prime(0));
+        assert!(!is_prime(1));
+        assert!(is_prime(2));
+        assert!(is_prime(3));
+        assert!(!is_prime(4));
+    }
+
+    #[test]
+    fn is_prime_agrees_across_the_sieve_dispatch_boundary() {
+        let mut set = PrimeSet::new();
+        for n in (IS_PRIME_SIEVE_LIMIT - 5)..=(IS_PRIME_SIEVE_LIMIT + 5) {
+            assert_eq!(set.is_prime(n), is_prime_mr(n), "mismatch at n = {}", n);
+        }
+    }
+
+    #[test]
+    fn primes_in_range_matches_brute_force() {
+        let expected: Vec<u64> = (0..200).filter(|&n| is_prime(n)).collect();
+        let actual: Vec<u64> = primes_in_range(0, 200).collect();
+        assert_eq!(actual, expected);
+    }
+
+    #[test]
+    fn primes_in_range_handles_empty_ranges() {
+        assert_eq!(primes_in_range(10, 10).collect::<Vec<_>>(), Vec::<u64>::new());
+        assert_eq!(primes_in_range(10, 0).collect::<Vec<_>>(), Vec::<u64>::new());
+    }
+
+    #[test]
+    fn count_in_range_matches_primes_in_range_length() {
+        assert_eq!(count_in_range(0, 1000), primes_in_range(0, 1000).count());
+    }
+}
@@ -0,0 +1,127 @@
+// A standalone primality test that doesn't need a sieve: deterministic
+// Miller-Rabin, valid for every u64. Useful for `is_prime` on large n, where
+// sieving up to sqrt(n) would be wasteful.
+
+/// Witnesses proven to correctly classify every `u64` (Jim Sinclair's
+/// deterministic witness set for the Miller-Rabin test).
+const WITNESSES: [u64; 7] = [2, 325, 9375, 28178, 450775, 9780504, 1795265022];
+
+/// Deterministic Miller-Rabin primality test.
+///
+/// Writes `n - 1 = d * 2^s` with `d` odd, then for each witness `a`, checks
+/// that `a^d mod n` is either `1` or `n - 1`, or reaches `n - 1` after
+/// repeated squaring; if no witness refutes `n`, it's prime.
+pub fn is_prime_mr(n: u64) -> bool {
+    if n < 2 {
+        return false;
+    }
+    if n.is_multiple_of(2) {
+        return n == 2;
+    }
+
+    let mut d = n - 1;
+    let mut s = 0u32;
+    while d.is_multiple_of(2) {
+        d /= 2;
+        s += 1;
+    }
+
+    'witness: for &a in WITNESSES.iter() {
+        if a.is_multiple_of(n) {
+            // n is (or divides) this witness; it can't refute itself.
+            continue;
+        }
+
+        let mut x = mod_pow(a, d, n);
+        if x == 1 || x == n - 1 {
+            continue;
+        }
+
+        for _ in 0..s - 1 {
+            x = mod_mul(x, x, n);
+            if x == n - 1 {
+                continue 'witness;
+            }
+        }
+
+        return false;
+    }
+
+    true
+}
+
+fn mod_mul(a: u64, b: u64, m: u64) -> u64 {
+    ((a as u128 * b as u128) % m as u128) as u64
+}
+
+fn mod_pow(mut base: u64, mut exponent: u64, m: u64) -> u64 {
+    let mut result = 1u64;
+    base %= m;
+    while exponent > 0 {
+        if exponent & 1 == 1 {
+            result = mod_mul(result, base, m);
+        }
+        base = mod_mul(base, base, m);
+        exponent >>= 1;
+    }
+    result
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn is_prime_naive(n: u64) -> bool {
+        if n < 2 {
+            return false;
+        }
+        let mut d = 2;
+        while d * d <= n {
+            if n.is_multiple_of(d) {
+                return false;
+            }
+            d += 1;
+        }
+        true
+    }
+
+    #[test]
+    fn rejects_below_two() {
+        assert!(!is_prime_mr(0));
+        assert!(!is_prime_mr(1));
+    }
+
+    #[test]
+    fn handles_two_and_other_evens() {
+        assert!(is_prime_mr(2));
+        assert!(!is_prime_mr(4));
+        assert!(!is_prime_mr(1_000_000));
+    }
+
+    #[test]
+    fn matches_naive_primality_for_small_n() {
+        for n in 0..10_000u64 {
+            assert_eq!(is_prime_mr(n), is_prime_naive(n), "mismatch at n = {}", n);
+        }
+    }
+
+    #[test]
+    fn classifies_witnesses_and_their_small_prime_divisors_correctly() {
+        // All witnesses but 2 itself are composite, so they must still be
+        // refuted...
+        for &a in WITNESSES.iter().filter(|&&a| a != 2) {
+            assert!(!is_prime_mr(a), "witness {} should not be classified prime", a);
+        }
+        // ...but a prime that happens to divide a witness takes the
+        // `a.is_multiple_of(n)` skip path and must still come out prime.
+        assert!(is_prime_mr(5)); // divides 325 and 9375
+        assert!(is_prime_mr(13)); // divides 325
+    }
+
+    #[test]
+    fn handles_large_known_primes_and_composites() {
+        assert!(is_prime_mr(1_000_000_007));
+        assert!(!is_prime_mr(1_000_000_000));
+        assert!(is_prime_mr(18_446_744_073_709_551_557)); // largest prime < 2^64
+    }
+}
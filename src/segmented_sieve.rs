@@ -0,0 +1,236 @@
+// A segmented Sieve of Eratosthenes for the common "give me all primes up to
+// some limit" case. `Primes`/`BiggerPrimes` generate primes incrementally
+// with no upper bound, which means per-candidate priority-queue churn; here
+// we know the bound up front, so we sieve it in cache-sized windows instead.
+//
+// The approach is the classic segmented sieve: first find the "base primes"
+// up to sqrt(limit) with a plain bit array, then walk [0, limit] in windows
+// small enough to stay resident in L1 data cache. For each window, strike
+// out multiples of every base prime (remembering where we left off, so we
+// don't recompute p*p or rescan from the window start each time), then read
+// off the survivors.
+
+// One bit per odd candidate (even numbers are skipped entirely), sized so a
+// segment's bit array fits comfortably in a 32 KiB L1 data cache.
+const SEGMENT_BYTES: usize = 1 << 15;
+const ODDS_PER_SEGMENT: usize = SEGMENT_BYTES * 8;
+
+// The number of consecutive integers a segment spans (odds only, so twice the
+// bit count). Exposed so other sieves built on the same base-prime striking
+// (e.g. `factor::least_factor_sieve`) can window themselves to match.
+pub(crate) const SEGMENT_SPAN: u64 = ODDS_PER_SEGMENT as u64 * 2;
+
+// A small fixed-size bit array. The segmented sieve indexes it one bit per
+// odd candidate in the current segment; `base_primes` one bit per integer.
+struct BitArray {
+    bits: Vec<u8>,
+}
+
+impl BitArray {
+    fn new(len: usize) -> BitArray {
+        BitArray {
+            bits: vec![0u8; len.div_ceil(8)],
+        }
+    }
+
+    fn is_set(&self, i: usize) -> bool {
+        self.bits[i / 8] & (1 << (i % 8)) != 0
+    }
+
+    fn set(&mut self, i: usize) {
+        self.bits[i / 8] |= 1 << (i % 8);
+    }
+}
+
+// Sieve all primes up to and including `limit` with a plain (non-segmented)
+// bit array. Used to find the base primes up to sqrt(limit), which is small
+// enough that cache residency doesn't matter.
+pub(crate) fn base_primes(limit: u64) -> Vec<u64> {
+    if limit < 2 {
+        return Vec::new();
+    }
+
+    let limit = limit as usize;
+    let mut bits = BitArray::new(limit + 1);
+    let mut primes = Vec::new();
+
+    for n in 2..=limit {
+        if !bits.is_set(n) {
+            primes.push(n as u64);
+            let mut m = n * n;
+            while m <= limit {
+                bits.set(m);
+                m += n;
+            }
+        }
+    }
+
+    primes
+}
+
+// A base prime's striking state, carried across segments so each window
+// picks up exactly where the last one left off. Shared with other sieves
+// built on the same windowed striking (e.g. `factor::least_factor_sieve`).
+pub(crate) struct BasePrime {
+    pub(crate) prime: u64,
+    pub(crate) next_multiple: u64,
+}
+
+/// An iterator over all primes `<= limit`, computed with a segmented Sieve of
+/// Eratosthenes.
+///
+/// Implements the same `Iterator<Item = u64>` interface as `Primes`, so
+/// callers can pick whichever backend fits: `SegmentedSieve` for "all primes
+/// up to N" (the common bounded case), `Primes` for an open-ended stream.
+pub struct SegmentedSieve {
+    limit: u64,
+    base_primes: Vec<BasePrime>,
+    segment_lo: u64,
+    segment_bits: BitArray,
+    segment_len: usize,
+    cursor: usize,
+    emitted_two: bool,
+}
+
+impl SegmentedSieve {
+    pub fn new(limit: u64) -> SegmentedSieve {
+        let sqrt_limit = (limit as f64).sqrt() as u64 + 1;
+        let base_primes = base_primes(sqrt_limit.min(limit))
+            .into_iter()
+            .filter(|&p| p != 2)
+            .map(|p| {
+                let square = p * p;
+                // Keep next_multiple odd, since the segment only tracks odd
+                // candidates.
+                let start = if square % 2 == 0 { square + p } else { square };
+                BasePrime {
+                    prime: p,
+                    next_multiple: start,
+                }
+            })
+            .collect();
+
+        let mut sieve = SegmentedSieve {
+            limit,
+            base_primes,
+            segment_lo: 3,
+            segment_bits: BitArray::new(0),
+            segment_len: 0,
+            cursor: 0,
+            emitted_two: false,
+        };
+        sieve.fill_segment();
+        sieve
+    }
+
+    // Sieve the window starting at `self.segment_lo`, striking multiples of
+    // every base prime and leaving survivors readable via `segment_bits`.
+    fn fill_segment(&mut self) {
+        let remaining_odds = if self.limit >= self.segment_lo {
+            ((self.limit - self.segment_lo) / 2 + 1) as usize
+        } else {
+            0
+        };
+        self.segment_len = remaining_odds.min(ODDS_PER_SEGMENT);
+        self.cursor = 0;
+
+        if self.segment_len == 0 {
+            return;
+        }
+
+        self.segment_bits = BitArray::new(self.segment_len);
+        let hi = self.segment_lo + 2 * self.segment_len as u64; // exclusive
+
+        for bp in &mut self.base_primes {
+            let mut m = bp.next_multiple;
+            let stride = 2 * bp.prime;
+            while m < hi {
+                let idx = ((m - self.segment_lo) / 2) as usize;
+                self.segment_bits.set(idx);
+                m += stride;
+            }
+            bp.next_multiple = m;
+        }
+    }
+}
+
+impl Iterator for SegmentedSieve {
+    type Item = u64;
+
+    fn next(&mut self) -> Option<u64> {
+        if !self.emitted_two {
+            self.emitted_two = true;
+            if self.limit >= 2 {
+                return Some(2);
+            }
+        }
+
+        loop {
+            while self.cursor < self.segment_len {
+                let idx = self.cursor;
+                self.cursor += 1;
+                if !self.segment_bits.is_set(idx) {
+                    return Some(self.segment_lo + 2 * idx as u64);
+                }
+            }
+
+            if self.segment_len == 0 {
+                return None;
+            }
+
+            self.segment_lo += 2 * self.segment_len as u64;
+            self.fill_segment();
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    // Independent, unoptimized primality check to validate the sieve against.
+    fn is_prime_naive(n: u64) -> bool {
+        if n < 2 {
+            return false;
+        }
+        let mut d = 2;
+        while d * d <= n {
+            if n.is_multiple_of(d) {
+                return false;
+            }
+            d += 1;
+        }
+        true
+    }
+
+    #[test]
+    fn matches_naive_primality_up_to_small_limit() {
+        let expected: Vec<u64> = (0..=1000).filter(|&n| is_prime_naive(n)).collect();
+        let actual: Vec<u64> = SegmentedSieve::new(1000).collect();
+        assert_eq!(actual, expected);
+    }
+
+    #[test]
+    fn handles_limits_below_the_first_prime() {
+        assert_eq!(SegmentedSieve::new(0).collect::<Vec<_>>(), Vec::<u64>::new());
+        assert_eq!(SegmentedSieve::new(1).collect::<Vec<_>>(), Vec::<u64>::new());
+        assert_eq!(SegmentedSieve::new(2).collect::<Vec<_>>(), vec![2]);
+        assert_eq!(SegmentedSieve::new(3).collect::<Vec<_>>(), vec![2, 3]);
+    }
+
+    #[test]
+    fn crosses_a_segment_boundary_without_dropping_or_duplicating_primes() {
+        // ODDS_PER_SEGMENT odd numbers per segment, so this limit spans
+        // several full segments plus a partial one.
+        let limit = 2 * ODDS_PER_SEGMENT as u64 * 3 + 17;
+        let expected: Vec<u64> = (0..=limit).filter(|&n| is_prime_naive(n)).collect();
+        let actual: Vec<u64> = SegmentedSieve::new(limit).collect();
+        assert_eq!(actual, expected);
+    }
+
+    #[test]
+    fn base_primes_matches_naive_primality() {
+        let expected: Vec<u64> = (0..=500).filter(|&n| is_prime_naive(n)).collect();
+        assert_eq!(base_primes(500), expected);
+    }
+}